@@ -0,0 +1,69 @@
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+// Mirrors the `[book]` table of mdBook's `book.toml`. Only the fields this tool cares about
+// are modelled; mdBook itself recognises more (e.g. `language`, `description`).
+#[derive(Debug, Default, Deserialize)]
+struct BookSection {
+    src: Option<String>,
+    #[allow(dead_code)]
+    title: Option<String>,
+    #[allow(dead_code)]
+    #[serde(default)]
+    authors: Vec<String>,
+}
+
+// An optional `[summary-generate]` table in `book.toml` holding defaults for this tool's own
+// flags, used where the matching flag isn't passed on the CLI and for every setting when running
+// as an mdBook preprocessor. A run with no arguments at all is a preprocessor run (see `main`), so
+// a direct CLI run still passes at least one argument.
+#[derive(Debug, Default, Deserialize)]
+struct SummaryGenerateSection {
+    trim_str: Option<String>,
+    title_from_name: Option<bool>,
+    create_readmes: Option<bool>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct BookToml {
+    #[serde(default)]
+    book: BookSection,
+    #[serde(rename = "summary-generate", default)]
+    summary_generate: SummaryGenerateSection,
+}
+
+impl BookToml {
+    pub fn src(&self) -> Option<PathBuf> {
+        self.book.src.as_ref().map(PathBuf::from)
+    }
+
+    pub fn trim_str(&self) -> Option<String> {
+        self.summary_generate.trim_str.clone()
+    }
+
+    pub fn title_from_name(&self) -> Option<bool> {
+        self.summary_generate.title_from_name
+    }
+
+    pub fn create_readmes(&self) -> Option<bool> {
+        self.summary_generate.create_readmes
+    }
+}
+
+// Walks upward from `start_dir` looking for a `book.toml`, parsing the first one found.
+// Returns the directory it was found in (`book.toml`'s `src` and this tool's own paths are all
+// relative to that directory, not to `start_dir`) alongside the parsed config. Returns `None` if
+// no `book.toml` is found or it fails to parse.
+pub fn find_book_toml(start_dir: &Path) -> Option<(PathBuf, BookToml)> {
+    let mut dir = Some(start_dir.to_path_buf());
+    while let Some(current) = dir {
+        let candidate = current.join("book.toml");
+        if candidate.is_file() {
+            let contents = std::fs::read_to_string(&candidate).ok()?;
+            let book_toml = toml::from_str(&contents).ok()?;
+            return Some((current, book_toml));
+        }
+        dir = current.parent().map(|p| p.to_path_buf());
+    }
+    None
+}
@@ -0,0 +1,68 @@
+use std::fmt;
+
+// Everything that can go wrong while walking the doc tree and generating SUMMARY.md, surfaced
+// as a typed error instead of a panic so the crate is safe to embed in build scripts and tests.
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    Glob(glob::GlobError),
+    Pattern(glob::PatternError),
+    StripPrefix(std::path::StripPrefixError),
+    /// A path or file name wasn't valid UTF-8, or otherwise couldn't be interpreted.
+    InvalidPath(String),
+    /// A combination of `GenerateConfig` options that doesn't make sense together, e.g. `merge`
+    /// with `nested` (there's no nested SUMMARY.md syntax to merge into).
+    UnsupportedCombination(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "I/O error: {}", e),
+            Error::Glob(e) => write!(f, "glob error: {}", e),
+            Error::Pattern(e) => write!(f, "invalid glob pattern: {}", e),
+            Error::StripPrefix(e) => write!(f, "path is not under base_path: {}", e),
+            Error::InvalidPath(p) => write!(f, "invalid path: {}", p),
+            Error::UnsupportedCombination(msg) => write!(f, "unsupported option combination: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Glob(e) => Some(e),
+            Error::Pattern(e) => Some(e),
+            Error::StripPrefix(e) => Some(e),
+            Error::InvalidPath(_) => None,
+            Error::UnsupportedCombination(_) => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<glob::GlobError> for Error {
+    fn from(e: glob::GlobError) -> Self {
+        Error::Glob(e)
+    }
+}
+
+impl From<glob::PatternError> for Error {
+    fn from(e: glob::PatternError) -> Self {
+        Error::Pattern(e)
+    }
+}
+
+impl From<std::path::StripPrefixError> for Error {
+    fn from(e: std::path::StripPrefixError) -> Self {
+        Error::StripPrefix(e)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,93 @@
+use std::path::{Path, PathBuf};
+
+use glob::{MatchOptions, Pattern};
+
+use crate::error::{Error, Result};
+
+// Controls which files under a directory count as doc pages: which extensions to glob for
+// (`--ext`), whether matching is case-insensitive (`--case-insensitive`), and which matched paths
+// get filtered back out by a repeatable `--ignore <glob>`.
+pub struct FileMatcher {
+    extensions: Vec<String>,
+    match_options: MatchOptions,
+    ignore: Vec<Pattern>,
+}
+
+impl FileMatcher {
+    // `md` is always matched; `extensions` adds further extensions on top of it (e.g. passing
+    // `markdown` via `--ext` matches both `.md` and `.markdown` files, never `.md` alone).
+    pub fn new(extensions: &[String], case_insensitive: bool, ignore: &[String]) -> Result<Self> {
+        let mut all_extensions = vec![String::from("md")];
+        for ext in extensions {
+            if !all_extensions.contains(ext) {
+                all_extensions.push(ext.clone());
+            }
+        }
+
+        let ignore = ignore.iter()
+            .map(|p| Pattern::new(p))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(FileMatcher {
+            extensions: all_extensions,
+            match_options: MatchOptions {
+                case_sensitive: !case_insensitive,
+                require_literal_separator: false,
+                require_literal_leading_dot: false,
+            },
+            ignore,
+        })
+    }
+
+    // Globs the direct children of `dir` for every configured extension.
+    pub fn glob_dir(&self, dir: &Path) -> Result<Vec<PathBuf>> {
+        self.glob_with_infix(dir, "/*.")
+    }
+
+    // Globs `dir` and all its descendants for every configured extension.
+    pub fn glob_tree(&self, dir: &Path) -> Result<Vec<PathBuf>> {
+        self.glob_with_infix(dir, "/**/*.")
+    }
+
+    fn glob_with_infix(&self, dir: &Path, infix: &str) -> Result<Vec<PathBuf>> {
+        let dir_str = dir.to_str().ok_or_else(|| Error::InvalidPath(dir.display().to_string()))?;
+        let mut matches = Vec::new();
+        for ext in &self.extensions {
+            let pattern = format!("{}{}{}", dir_str, infix, ext);
+            for entry in glob::glob_with(&pattern, self.match_options)? {
+                let path = entry?;
+                if !self.is_ignored(&path) {
+                    matches.push(path);
+                }
+            }
+        }
+        Ok(matches)
+    }
+
+    // True if `path` matches one of the `--ignore` patterns.
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        self.ignore.iter().any(|pattern| pattern.matches_path_with(path, self.match_options))
+    }
+
+    // True if `dir` itself should be skipped while walking directories (`--nested`'s subdirectory
+    // recursion, `--create_readmes`'s directory walk). A pattern like `**/node_modules/**` only
+    // matches paths with something *after* `node_modules/`, so it never matches the bare
+    // directory path; also checking a trailing-slash form of `dir` catches that directory-only
+    // case too.
+    pub fn is_ignored_dir(&self, dir: &Path) -> bool {
+        self.is_ignored(dir) || self.is_ignored(&dir.join(""))
+    }
+
+    // Finds `dir`'s README, trying each configured extension in order.
+    pub fn find_readme(&self, dir: &Path) -> Option<PathBuf> {
+        self.extensions.iter()
+            .map(|ext| dir.join(format!("README.{}", ext)))
+            .find(|candidate| candidate.is_file())
+    }
+
+    // The README file name to synthesize for a directory that doesn't have one yet, using the
+    // first configured extension.
+    pub fn default_readme_name(&self) -> String {
+        format!("README.{}", self.extensions[0])
+    }
+}
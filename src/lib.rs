@@ -0,0 +1,337 @@
+use std::path::{Path, PathBuf};
+
+use glob::glob;
+use itertools::Itertools;
+
+pub use crate::book_config::{find_book_toml, BookToml};
+pub use crate::error::{Error, Result};
+pub use crate::file_matcher::FileMatcher;
+pub use crate::summary_entry::SummaryEntry;
+pub use crate::summary_merge::merge_summary;
+
+mod book_config;
+mod error;
+mod file_matcher;
+pub mod preprocessor;
+mod summary_entry;
+mod summary_merge;
+mod title;
+
+const HEADER: &str = "# https://github.com/rust-lang-nursery/mdBook/issues/677";
+
+// Configures one `generate_summary` run. Mirrors the CLI flags one-for-one so the binary is a
+// thin wrapper around this struct.
+pub struct GenerateConfig {
+    pub base_path: PathBuf,
+    pub trim_str: String,
+    pub title_from_name: bool,
+    pub create_readmes: bool,
+    pub nested: bool,
+    pub merge: bool,
+    /// Extra file extensions to match, on top of the always-matched `md` (see `FileMatcher::new`).
+    pub extensions: Vec<String>,
+    pub case_insensitive: bool,
+    pub ignore: Vec<String>,
+}
+
+impl Default for GenerateConfig {
+    fn default() -> Self {
+        GenerateConfig {
+            base_path: PathBuf::from("src/"),
+            trim_str: String::from("# "),
+            title_from_name: false,
+            create_readmes: false,
+            nested: false,
+            merge: false,
+            extensions: Vec::new(),
+            case_insensitive: false,
+            ignore: Vec::new(),
+        }
+    }
+}
+
+// The subset of `GenerateConfig` that can come either from an explicit CLI flag or from
+// `book.toml`'s `[book]`/`[summary-generate]` tables. `None`/`false` means "not explicitly set",
+// as opposed to a value the caller deliberately chose that happens to match the default.
+#[derive(Default)]
+pub struct ConfigOverrides {
+    pub base_path: Option<PathBuf>,
+    pub trim_str: Option<String>,
+    pub title_from_name: bool,
+    pub create_readmes: bool,
+}
+
+// Merges `overrides` with `book_toml`'s settings, falling back to `GenerateConfig::default()`'s
+// built-in values when neither is set. An explicit override always wins over `book.toml`. Used
+// by both the CLI entry point and the mdBook preprocessor entry point so they resolve
+// `base_path`/`trim_str`/`title_from_name`/`create_readmes` identically.
+pub fn resolve_book_settings(overrides: ConfigOverrides, book_toml: Option<&BookToml>) -> GenerateConfig {
+    let defaults = GenerateConfig::default();
+
+    let base_path = overrides.base_path
+        .or_else(|| book_toml.and_then(|b| b.src()))
+        .unwrap_or(defaults.base_path);
+    let trim_str = overrides.trim_str
+        .or_else(|| book_toml.and_then(|b| b.trim_str()))
+        .unwrap_or(defaults.trim_str);
+    let title_from_name = overrides.title_from_name
+        || book_toml.and_then(|b| b.title_from_name()).unwrap_or(defaults.title_from_name);
+    let create_readmes = overrides.create_readmes
+        || book_toml.and_then(|b| b.create_readmes()).unwrap_or(defaults.create_readmes);
+
+    GenerateConfig { base_path, trim_str, title_from_name, create_readmes, ..defaults }
+}
+
+// Walks the doc tree rooted at `config.base_path` and returns the SUMMARY.md body that should
+// be written, without touching SUMMARY.md itself — callers decide how (or whether) to persist
+// it, which keeps this function usable from build scripts and tests alike.
+pub fn generate_summary(config: &GenerateConfig) -> Result<String> {
+    if config.merge && config.nested {
+        return Err(Error::UnsupportedCombination(
+            "--merge cannot be combined with --nested (there's no nested SUMMARY.md syntax to merge into)".to_string(),
+        ));
+    }
+
+    let matcher = FileMatcher::new(&config.extensions, config.case_insensitive, &config.ignore)?;
+
+    if config.nested {
+        let tree = build_tree(&config.base_path, &config.base_path, &config.trim_str, config.title_from_name, &matcher)?;
+        let mut lines = vec![HEADER.to_string()];
+        write_tree(&tree, 0, &mut lines);
+        return Ok(lines.join("\n") + "\n");
+    }
+
+    let sum_entries = if config.create_readmes {
+        collect_with_readmes(&config.base_path, &config.trim_str, config.title_from_name, &matcher)?
+    } else {
+        collect_flat(&config.base_path, &config.trim_str, config.title_from_name, &matcher)?
+    };
+
+    if config.merge {
+        let sorted_entries: Vec<SummaryEntry> = sum_entries.into_iter().sorted().collect();
+        return Ok(merge_summary(&config.base_path.join("SUMMARY.md"), &config.base_path, &sorted_entries));
+    }
+
+    let mut lines = vec![HEADER.to_string()];
+    lines.extend(sum_entries.into_iter().sorted().map(|e| e.summary_line()));
+    Ok(lines.join("\n") + "\n")
+}
+
+// For a given path (*.md), returns a Summary entry.
+// `title_from_name` - true : get title from file name, if it is README.md, gets the dir name.
+// `title_from_name` - false : get title from front matter, the 1st level-1 heading, or `trim_str`.
+fn find_content(path: &Path, base_path: &Path, trim_str: &str, title_from_name: bool) -> Result<Option<SummaryEntry>> {
+    if title_from_name {
+        let stem = path.file_stem()
+            .and_then(|s| s.to_str())
+            .ok_or_else(|| Error::InvalidPath(path.display().to_string()))?;
+        let title = if stem == "README" {
+            relative_path(path, base_path)?
+                .parent()
+                .and_then(|p| p.file_name())
+                .and_then(|n| n.to_str())
+                .map(String::from)
+                .unwrap_or_else(|| stem.to_string())
+        } else {
+            stem.to_string()
+        };
+        return Ok(Some(SummaryEntry { path: relative_path(path, base_path)?, title }));
+    }
+
+    // else get title from inside the file
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+
+    match title::extract_title(&contents, trim_str) {
+        Some(title) => Ok(Some(SummaryEntry { path: relative_path(path, base_path)?, title })),
+        None => Ok(None),
+    }
+}
+
+// Build the vector of Summary entries for a given dir.
+fn handle_dir(path: &Path, base_path: &Path, trim_str: &str, title_from_name: bool, matcher: &FileMatcher, sum_entries: &mut Vec<SummaryEntry>) -> Result<()> {
+    // Handle matched files in directory
+    for entry in matcher.glob_dir(path)? {
+        if let Some(sum_line) = find_content(&entry, base_path, trim_str, title_from_name)? {
+            sum_entries.push(sum_line);
+        }
+    }
+
+    // Handle missing README (to get a decent tree in SUMMARY.md)
+    if matcher.find_readme(path).is_none() {
+        let mut readme_path = relative_path(path, base_path)?;
+        let title = readme_path.file_name()
+            .and_then(|n| n.to_str())
+            .map(String::from)
+            .unwrap_or_else(|| String::from("README"));
+        readme_path.push(matcher.default_readme_name());
+        sum_entries.push(SummaryEntry { path: readme_path, title });
+    }
+
+    Ok(())
+}
+
+// Builds a dir/file's path relative to `base_path`.
+fn relative_path(path: &Path, base_path: &Path) -> Result<PathBuf> {
+    Ok(path.strip_prefix(base_path)?.to_path_buf())
+}
+
+fn path_to_glob_str(path: &Path) -> Result<String> {
+    path.to_str()
+        .map(String::from)
+        .ok_or_else(|| Error::InvalidPath(path.display().to_string()))
+}
+
+// A node in the nested SUMMARY.md tree: mirrors one directory, linked via its README, with
+// its sibling matched files and subdirectories nested beneath it as children.
+struct SummaryNode {
+    entry: SummaryEntry,
+    children: Vec<SummaryNode>,
+}
+
+// Recursively builds a `SummaryNode` tree rooted at `dir`: `dir`'s own README becomes the
+// node's entry, its sibling matched files become leaf children, and its subdirectories become
+// children built the same way, one level deeper.
+fn build_tree(dir: &Path, base_path: &Path, trim_str: &str, title_from_name: bool, matcher: &FileMatcher) -> Result<SummaryNode> {
+    let readme_path = matcher.find_readme(dir);
+    let entry = match &readme_path {
+        Some(readme_path) => find_content(readme_path, base_path, trim_str, title_from_name)?,
+        None => None,
+    }.unwrap_or_else(|| {
+        let rel = relative_path(dir, base_path).unwrap_or_default();
+        let title = rel.file_name().and_then(|n| n.to_str()).map(String::from).unwrap_or_else(|| String::from("README"));
+        SummaryEntry { path: rel.join(matcher.default_readme_name()), title }
+    });
+
+    let mut children: Vec<SummaryNode> = Vec::new();
+
+    for path in matcher.glob_dir(dir)? {
+        if Some(&path) == readme_path.as_ref() {
+            continue;
+        }
+        if let Some(entry) = find_content(&path, base_path, trim_str, title_from_name)? {
+            children.push(SummaryNode { entry, children: Vec::new() });
+        }
+    }
+
+    let glob_string = path_to_glob_str(dir)? + "/*/";
+    for subdir in glob(&glob_string)? {
+        let subdir = subdir?;
+        if matcher.is_ignored_dir(&subdir) {
+            continue;
+        }
+        children.push(build_tree(&subdir, base_path, trim_str, title_from_name, matcher)?);
+    }
+
+    children.sort_by(|a, b| a.entry.cmp(&b.entry));
+
+    Ok(SummaryNode { entry, children })
+}
+
+// Walks the nested tree depth-first, prefixing each `summary_line()` with four spaces per
+// level of depth, so the generated SUMMARY.md produces collapsible nested chapters.
+fn write_tree(node: &SummaryNode, depth: usize, lines: &mut Vec<String>) {
+    let indent = "    ".repeat(depth);
+    lines.push(format!("{}{}", indent, node.entry.summary_line()));
+    for child in &node.children {
+        write_tree(child, depth + 1, lines);
+    }
+}
+
+// Collects every matched file under `base_path`, with a synthetic README entry per directory
+// that's missing one, mirroring the `--create_readmes` CLI behaviour.
+fn collect_with_readmes(base_path: &Path, trim_str: &str, title_from_name: bool, matcher: &FileMatcher) -> Result<Vec<SummaryEntry>> {
+    let mut sum_entries = vec![SummaryEntry { path: PathBuf::from(matcher.default_readme_name()), title: String::from("README") }];
+
+    let glob_string = path_to_glob_str(base_path)? + "/**/";
+    for path in glob(&glob_string)?.filter_map(|e| e.ok()) {
+        if matcher.is_ignored_dir(&path) {
+            continue;
+        }
+        handle_dir(&path, base_path, trim_str, title_from_name, matcher, &mut sum_entries)?;
+    }
+
+    Ok(sum_entries)
+}
+
+// Collects every matched file under `base_path` into a flat list, one entry per file.
+fn collect_flat(base_path: &Path, trim_str: &str, title_from_name: bool, matcher: &FileMatcher) -> Result<Vec<SummaryEntry>> {
+    let mut sum_entries = Vec::new();
+    for path in matcher.glob_tree(base_path)? {
+        if let Some(entry) = find_content(&path, base_path, trim_str, title_from_name)? {
+            sum_entries.push(entry);
+        }
+    }
+    Ok(sum_entries)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn find_content_finds_a_title() {
+        use std::io::Write;
+
+        let mut file = NamedTempFile::new().expect("should have created a tempfile");
+        writeln!(file, "\n\n# A File").expect("should have written to file");
+
+        let entry = find_content(&file.path().to_path_buf(), &file.path().parent().unwrap().to_path_buf(), "# ", false)
+            .expect("should not error");
+
+        assert_eq!(entry.is_none(), false);
+
+        if let Some(ref entry) = entry {
+            assert_eq!(entry.title, "A File".to_string());
+        }
+    }
+
+    #[test]
+    fn find_content_returns_no_title_if_none_found() {
+        use std::io::Write;
+
+        let mut file = NamedTempFile::new().expect("should have created a tempfile");
+        writeln!(file, "\n\n").expect("should have written to file");
+
+        let entry = find_content(&file.path().to_path_buf(), &file.path().parent().unwrap().to_path_buf(), "# ", false)
+            .expect("should not error");
+
+        assert_eq!(entry.is_none(), true);
+    }
+
+    #[test]
+    fn build_tree_nests_files_under_their_directory() {
+        let dir = tempfile::tempdir().expect("should have created a tempdir");
+        std::fs::write(dir.path().join("README.md"), "# Root").expect("should have written to file");
+        std::fs::write(dir.path().join("a.md"), "# A").expect("should have written to file");
+        std::fs::create_dir(dir.path().join("sub")).expect("should have created a dir");
+        std::fs::write(dir.path().join("sub").join("README.md"), "# Sub").expect("should have written to file");
+        std::fs::write(dir.path().join("sub").join("b.md"), "# B").expect("should have written to file");
+
+        let matcher = FileMatcher::new(&[], false, &[]).expect("should build a matcher");
+        let tree = build_tree(dir.path(), dir.path(), "# ", false, &matcher).expect("should build a tree");
+
+        assert_eq!(tree.entry.title, "Root");
+
+        let mut lines = Vec::new();
+        write_tree(&tree, 0, &mut lines);
+
+        assert_eq!(lines[0], "- [Root](README.md)");
+        assert!(lines.contains(&String::from("    - [A](a.md)")));
+        assert!(lines.contains(&String::from("    - [Sub](sub/README.md)")));
+        assert!(lines.contains(&String::from("        - [B](sub/b.md)")));
+    }
+
+    #[test]
+    fn generate_summary_rejects_merge_combined_with_nested() {
+        let config = GenerateConfig { merge: true, nested: true, ..GenerateConfig::default() };
+
+        let err = generate_summary(&config).expect_err("should reject merge + nested");
+
+        assert!(matches!(err, Error::UnsupportedCombination(_)));
+    }
+}
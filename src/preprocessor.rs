@@ -0,0 +1,42 @@
+use std::io::{self, Read};
+use std::path::PathBuf;
+
+use serde_json::Value;
+
+use crate::{find_book_toml, generate_summary, resolve_book_settings, ConfigOverrides, GenerateConfig};
+
+// mdBook asks every preprocessor whether it supports a given renderer before running it. This
+// preprocessor only rewrites SUMMARY.md, which every renderer consumes, so it always says yes.
+pub fn supports(_renderer: &str) -> i32 {
+    0
+}
+
+// Runs as an mdBook preprocessor: reads the `[PreprocessorContext, Book]` JSON tuple mdBook
+// feeds on stdin, regenerates SUMMARY.md from the doc tree on disk, and echoes the `Book` back
+// out unchanged on stdout so the render pipeline can continue.
+pub fn preprocess() -> Result<(), Box<dyn std::error::Error>> {
+    let mut input = String::new();
+    io::stdin().read_to_string(&mut input)?;
+
+    let mut pair: Value = serde_json::from_str(&input)?;
+    let (context, book) = match pair.as_array_mut() {
+        Some(items) if items.len() == 2 => (items.remove(0), items.remove(0)),
+        _ => return Err("expected mdBook's [context, book] JSON array on stdin".into()),
+    };
+
+    let root = context.get("root")
+        .and_then(Value::as_str)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("."));
+
+    let book_toml = find_book_toml(&root);
+    let book_dir = book_toml.as_ref().map(|(dir, _)| dir.clone()).unwrap_or(root);
+    let config = resolve_book_settings(ConfigOverrides::default(), book_toml.as_ref().map(|(_, b)| b));
+    let config = GenerateConfig { base_path: book_dir.join(&config.base_path), ..config };
+
+    let summary = generate_summary(&config)?;
+    std::fs::write(config.base_path.join("SUMMARY.md"), summary)?;
+
+    serde_json::to_writer(io::stdout(), &book)?;
+    Ok(())
+}
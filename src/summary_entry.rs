@@ -0,0 +1,15 @@
+use std::path::PathBuf;
+
+// A single line in the generated SUMMARY.md: a link title and the path it points to.
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Clone)]
+pub struct SummaryEntry {
+    pub path: PathBuf,
+    pub title: String,
+}
+
+impl SummaryEntry {
+    // Renders this entry as a markdown SUMMARY.md bullet, e.g. `- [Title](path/to/file.md)`.
+    pub fn summary_line(&self) -> String {
+        format!("- [{}]({})", self.title, self.path.display())
+    }
+}
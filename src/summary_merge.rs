@@ -0,0 +1,166 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::summary_entry::SummaryEntry;
+
+// One parsed line from an existing SUMMARY.md. `Link` lines are the ones this tool manages;
+// everything else (part titles, `---` separators, prefix/suffix chapter text) is passed through
+// untouched so hand-authored structure survives a `--merge` run.
+enum ParsedLine {
+    Link { indent: String, entry: SummaryEntry },
+    Other(String),
+}
+
+// Parses `summary` into a sequence of `ParsedLine`s, recognising mdBook's `- [Title](path)`
+// link syntax at any indentation depth and leaving everything else untouched.
+fn parse_summary(summary: &str) -> Vec<ParsedLine> {
+    summary.lines().map(|line| {
+        let trimmed = line.trim_start();
+        let indent = &line[..line.len() - trimmed.len()];
+        if let Some(rest) = trimmed.strip_prefix("- [") {
+            if let Some(close_bracket) = rest.find(']') {
+                let title = &rest[..close_bracket];
+                let after = &rest[close_bracket + 1..];
+                if let Some(path_rest) = after.strip_prefix('(') {
+                    if let Some(close_paren) = path_rest.find(')') {
+                        let path = &path_rest[..close_paren];
+                        return ParsedLine::Link {
+                            indent: indent.to_string(),
+                            entry: SummaryEntry {
+                                path: PathBuf::from(path),
+                                title: title.to_string(),
+                            },
+                        };
+                    }
+                }
+            }
+        }
+        ParsedLine::Other(line.to_string())
+    }).collect()
+}
+
+// Merges `new_entries` into the SUMMARY.md already present at `summary_path`: prefix chapters,
+// part titles and `---` separators are kept verbatim and in their existing order, links whose
+// target no longer exists under `base_path` are dropped, and entries not yet listed are inserted
+// in sorted-by-path order relative to the surviving links (not dumped in a trailing block), so a
+// freshly discovered page lands next to where it would have sorted. Returns the merged file body.
+pub fn merge_summary(summary_path: &Path, base_path: &Path, new_entries: &[SummaryEntry]) -> String {
+    let existing = fs::read_to_string(summary_path).unwrap_or_default();
+    let parsed = parse_summary(&existing);
+
+    let kept_paths: HashSet<PathBuf> = parsed.iter()
+        .filter_map(|line| match line {
+            ParsedLine::Link { entry, .. } if base_path.join(&entry.path).exists() => Some(entry.path.clone()),
+            _ => None,
+        })
+        .collect();
+
+    let mut to_insert: Vec<&SummaryEntry> = new_entries.iter()
+        .filter(|e| !kept_paths.contains(&e.path))
+        .collect();
+    to_insert.sort_by(|a, b| a.path.cmp(&b.path));
+    let mut to_insert = to_insert.into_iter().peekable();
+
+    let mut lines: Vec<String> = Vec::new();
+
+    for line in parsed {
+        match line {
+            ParsedLine::Link { indent, entry } => {
+                if base_path.join(&entry.path).exists() {
+                    while to_insert.peek().map_or(false, |next| next.path < entry.path) {
+                        lines.push(to_insert.next().unwrap().summary_line());
+                    }
+                    lines.push(format!("{}{}", indent, entry.summary_line()));
+                }
+                // else: target no longer exists, drop the line
+            }
+            ParsedLine::Other(line) => lines.push(line),
+        }
+    }
+
+    for entry in to_insert {
+        lines.push(entry.summary_line());
+    }
+
+    lines.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_summary_recognises_links_and_passes_through_everything_else() {
+        let summary = "# Summary\n\n- [Intro](intro.md)\n    - [Nested](nested/README.md)\n---\n";
+        let parsed = parse_summary(summary);
+
+        assert_eq!(parsed.len(), 5);
+
+        match &parsed[0] {
+            ParsedLine::Other(line) => assert_eq!(line, "# Summary"),
+            ParsedLine::Link { .. } => panic!("expected an Other line"),
+        }
+
+        match &parsed[2] {
+            ParsedLine::Link { indent, entry } => {
+                assert_eq!(indent, "");
+                assert_eq!(entry.title, "Intro");
+                assert_eq!(entry.path, PathBuf::from("intro.md"));
+            }
+            ParsedLine::Other(_) => panic!("expected a Link line"),
+        }
+
+        match &parsed[3] {
+            ParsedLine::Link { indent, entry } => {
+                assert_eq!(indent, "    ");
+                assert_eq!(entry.title, "Nested");
+                assert_eq!(entry.path, PathBuf::from("nested/README.md"));
+            }
+            ParsedLine::Other(_) => panic!("expected a Link line"),
+        }
+    }
+
+    #[test]
+    fn merge_summary_keeps_surviving_links_drops_stale_ones_and_appends_new_entries() {
+        let base_dir = tempfile::tempdir().expect("should have created a tempdir");
+        std::fs::write(base_dir.path().join("intro.md"), "# Intro").expect("should have written a file");
+        std::fs::write(base_dir.path().join("new.md"), "# New").expect("should have written a file");
+
+        let summary_path = base_dir.path().join("SUMMARY.md");
+        std::fs::write(&summary_path, "# Summary\n\n- [Intro](intro.md)\n- [Gone](gone.md)\n").expect("should have written SUMMARY.md");
+
+        let new_entries = vec![
+            SummaryEntry { path: PathBuf::from("intro.md"), title: String::from("Intro") },
+            SummaryEntry { path: PathBuf::from("new.md"), title: String::from("New") },
+        ];
+
+        let merged = merge_summary(&summary_path, base_dir.path(), &new_entries);
+
+        assert!(merged.contains("- [Intro](intro.md)"));
+        assert!(!merged.contains("gone.md"));
+        assert!(merged.contains("- [New](new.md)"));
+    }
+
+    #[test]
+    fn merge_summary_inserts_new_entries_in_sorted_position_instead_of_a_trailing_block() {
+        let base_dir = tempfile::tempdir().expect("should have created a tempdir");
+        std::fs::write(base_dir.path().join("aaa.md"), "# Aaa").expect("should have written a file");
+        std::fs::write(base_dir.path().join("mmm.md"), "# Mmm").expect("should have written a file");
+        std::fs::write(base_dir.path().join("zzz.md"), "# Zzz").expect("should have written a file");
+
+        let summary_path = base_dir.path().join("SUMMARY.md");
+        std::fs::write(&summary_path, "- [Aaa](aaa.md)\n- [Zzz](zzz.md)\n").expect("should have written SUMMARY.md");
+
+        let new_entries = vec![
+            SummaryEntry { path: PathBuf::from("aaa.md"), title: String::from("Aaa") },
+            SummaryEntry { path: PathBuf::from("mmm.md"), title: String::from("Mmm") },
+            SummaryEntry { path: PathBuf::from("zzz.md"), title: String::from("Zzz") },
+        ];
+
+        let merged = merge_summary(&summary_path, base_dir.path(), &new_entries);
+        let lines: Vec<&str> = merged.lines().collect();
+
+        assert_eq!(lines, vec!["- [Aaa](aaa.md)", "- [Mmm](mmm.md)", "- [Zzz](zzz.md)"]);
+    }
+}
@@ -0,0 +1,49 @@
+use pulldown_cmark::{Event, HeadingLevel, Parser, Tag};
+
+// Extracts a title from markdown `contents`: a `title:` key in a leading `---` delimited YAML
+// front-matter block takes priority, otherwise the text of the first level-1 heading is used,
+// parsed with `pulldown_cmark` so headings inside code spans/blocks are correctly ignored.
+// Falls back to a raw `trim_str`-prefixed line for documents using a non-heading marker.
+pub fn extract_title(contents: &str, trim_str: &str) -> Option<String> {
+    if let Some(title) = front_matter_title(contents) {
+        return Some(title);
+    }
+
+    if let Some(title) = first_h1(contents) {
+        return Some(title);
+    }
+
+    contents.lines()
+        .map(|line| line.trim())
+        .find(|line| line.starts_with(trim_str))
+        .map(|line| line.trim_start_matches(trim_str).to_string())
+}
+
+// Reads the `title:` key out of a leading `---\n...\n---` YAML front-matter block, if present.
+fn front_matter_title(contents: &str) -> Option<String> {
+    let rest = contents.strip_prefix("---\n")?;
+    let end = rest.find("\n---")?;
+    let front_matter = &rest[..end];
+
+    front_matter.lines()
+        .find_map(|line| line.strip_prefix("title:"))
+        .map(|value| value.trim().trim_matches(|c| c == '"' || c == '\'').to_string())
+}
+
+// Parses `contents` as CommonMark and returns the text of the first level-1 heading, if any.
+fn first_h1(contents: &str) -> Option<String> {
+    let parser = Parser::new(contents);
+    let mut in_h1 = false;
+    let mut title = String::new();
+
+    for event in parser {
+        match event {
+            Event::Start(Tag::Heading(HeadingLevel::H1, ..)) => in_h1 = true,
+            Event::End(Tag::Heading(HeadingLevel::H1, ..)) => return Some(title),
+            Event::Text(text) | Event::Code(text) if in_h1 => title.push_str(&text),
+            _ => {}
+        }
+    }
+
+    None
+}